@@ -1,13 +1,14 @@
 use clap::{Parser, Subcommand};
 use reqwest::blocking::{Client, multipart};
-use reqwest::header::{HeaderMap, HeaderName, HeaderValue, CONTENT_TYPE};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, CONTENT_TYPE};
+use base64::Engine;
 use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 use std::time::Duration;
 use anyhow::Result;
 
-/// HTTP CLI: Отправка запросов GET, POST, PUT, DELETE с возможностью добавления заголовков и отправки файлов.
+/// HTTP CLI: Отправка запросов GET, POST, PUT, PATCH, DELETE, HEAD с возможностью добавления заголовков и отправки файлов.
 ///
 /// # Примеры использования:
 ///
@@ -15,6 +16,7 @@ use anyhow::Result;
 /// ```sh
 /// http_client get http://example.com
 /// http_client get example.com -H "User-Agent=MyClient"
+/// http_client get example.com -q "foo=bar baz"
 /// ```
 ///
 /// ## POST-запрос:
@@ -38,19 +40,54 @@ use anyhow::Result;
 struct Cli {
     #[command(subcommand)]
     command: Commands,
+
+    /// Подробный вывод: метод, URL и заголовки запроса перед отправкой, статус и редиректы после
+    #[arg(short = 'v', long, global = true)]
+    verbose: bool,
+
+    /// Не следовать редиректам
+    #[arg(short = 'n', long = "nofollow", global = true)]
+    nofollow: bool,
+
+    /// Максимальное число редиректов, которое можно выполнить (игнорируется при --nofollow)
+    #[arg(long = "max-redirects", value_name = "N", global = true)]
+    max_redirects: Option<usize>,
+
+    /// Не форматировать JSON-ответы, выводить тело как есть
+    #[arg(short = 'r', long, global = true)]
+    raw: bool,
+
+    /// Базовая аутентификация в формате user:pass, кодируется в заголовок Authorization: Basic
+    #[arg(long = "basic", value_name = "USER:PASS", global = true)]
+    basic: Option<String>,
+
+    /// Токен для аутентификации, отправляется в заголовке Authorization: Bearer
+    #[arg(long = "bearer", value_name = "TOKEN", global = true)]
+    bearer: Option<String>,
+
+    /// Сохранить тело ответа в файл по указанному пути вместо вывода в stdout
+    #[arg(short = 'o', long = "output", value_name = "PATH", global = true, conflicts_with = "remote_name")]
+    output: Option<PathBuf>,
+
+    /// Сохранить тело ответа в файл, имя которого берётся из Content-Disposition или последнего сегмента URL
+    #[arg(short = 'O', global = true)]
+    remote_name: bool,
 }
 
 #[derive(Subcommand)]
 enum Commands {
     /// GET-запрос
     #[command(about = "Отправка GET-запроса на указанный URL.",
-        long_about = "Отправляет GET-запрос на указанный URL с возможностью указания заголовков.\n\nПример:\n\nhttp_client get http://example.com\nhttp_client get example.com -H \"User-Agent=MyClient\"")]
+        long_about = "Отправляет GET-запрос на указанный URL с возможностью указания заголовков и параметров запроса.\n\nПример:\n\nhttp_client get http://example.com\nhttp_client get example.com -H \"User-Agent=MyClient\"\nhttp_client get example.com -q \"foo=bar baz\"")]
     Get {
         #[arg(value_name = "URL")]
         url: String,
 
         #[arg(short = 'H', long, value_parser = parse_key_val)]
         headers: Vec<(String, String)>,
+
+        #[arg(short = 'q', long = "query", value_parser = parse_key_val)]
+        query: Vec<(String, String)>,
     },
 
     /// POST-запрос
@@ -68,6 +105,12 @@ enum Commands {
 
         #[arg(short = 'H', long, value_parser = parse_key_val)]
         headers: Vec<(String, String)>,
+
+        #[arg(short = 'q', long = "query", value_parser = parse_key_val)]
+        query: Vec<(String, String)>,
+
+        #[arg(short = 't', long = "content-type", conflicts_with = "file")]
+        content_type: Option<String>,
     },
 
     /// PUT-запрос
@@ -85,6 +128,35 @@ enum Commands {
 
         #[arg(short = 'H', long, value_parser = parse_key_val)]
         headers: Vec<(String, String)>,
+
+        #[arg(short = 'q', long = "query", value_parser = parse_key_val)]
+        query: Vec<(String, String)>,
+
+        #[arg(short = 't', long = "content-type", conflicts_with = "file")]
+        content_type: Option<String>,
+    },
+
+    /// PATCH-запрос
+    #[command(about = "Отправка PATCH-запроса с данными или файлом.",
+        long_about = "Отправляет PATCH-запрос на указанный URL. Можно отправить частичное обновление в формате JSON или загрузить файл.\n\nПример с данными:\n\nhttp_client patch http://example.com -d '{\"update\": true}'\n\nПример с файлом:\n\nhttp_client patch example.com -f update.txt")]
+    Patch {
+        #[arg(value_name = "URL")]
+        url: String,
+
+        #[arg(short, long)]
+        data: Option<String>,
+
+        #[arg(short, long)]
+        file: Option<PathBuf>,
+
+        #[arg(short = 'H', long, value_parser = parse_key_val)]
+        headers: Vec<(String, String)>,
+
+        #[arg(short = 'q', long = "query", value_parser = parse_key_val)]
+        query: Vec<(String, String)>,
+
+        #[arg(short = 't', long = "content-type", conflicts_with = "file")]
+        content_type: Option<String>,
     },
 
     /// DELETE-запрос
@@ -96,6 +168,23 @@ enum Commands {
 
         #[arg(short = 'H', long, value_parser = parse_key_val)]
         headers: Vec<(String, String)>,
+
+        #[arg(short = 'q', long = "query", value_parser = parse_key_val)]
+        query: Vec<(String, String)>,
+    },
+
+    /// HEAD-запрос
+    #[command(about = "Отправка HEAD-запроса на указанный URL.",
+        long_about = "Отправляет HEAD-запрос на указанный URL с возможностью указания заголовков. Тело ответа не запрашивается и не выводится.\n\nПример:\n\nhttp_client head http://example.com")]
+    Head {
+        #[arg(value_name = "URL")]
+        url: String,
+
+        #[arg(short = 'H', long, value_parser = parse_key_val)]
+        headers: Vec<(String, String)>,
+
+        #[arg(short = 'q', long = "query", value_parser = parse_key_val)]
+        query: Vec<(String, String)>,
     },
 }
 
@@ -107,16 +196,41 @@ fn parse_key_val(s: &str) -> Result<(String, String)> {
     Ok((parts[0].to_string(), parts[1].to_string()))
 }
 
-fn build_headers(headers: &[(String, String)]) -> HeaderMap {
+fn build_headers(headers: &[(String, String)], auth: &Option<String>) -> HeaderMap {
     let mut header_map = HeaderMap::new();
     for (key, value) in headers {
         if let (Ok(name), Ok(val)) = (HeaderName::from_bytes(key.as_bytes()), HeaderValue::from_str(value)) {
             header_map.insert(name, val);
         }
     }
+    if let Some(auth_value) = auth {
+        if !header_map.contains_key(AUTHORIZATION) {
+            if let Ok(val) = HeaderValue::from_str(auth_value) {
+                header_map.insert(AUTHORIZATION, val);
+            }
+        }
+    }
     header_map
 }
 
+/// Строит значение заголовка `Authorization` из `--basic user:pass` или `--bearer token`.
+/// `--basic` побеждает, если заданы оба флага.
+fn build_auth_header(basic: &Option<String>, bearer: &Option<String>) -> Option<String> {
+    if let Some(credentials) = basic {
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        Some(format!("Basic {}", encoded))
+    } else {
+        bearer.as_ref().map(|token| format!("Bearer {}", token))
+    }
+}
+
+/// Подставляет `http://` по умолчанию, если `url` не начинается явно с `http://`/`https://`.
+///
+/// Нельзя доверять одному только результату `reqwest::Url::parse`: он охотно принимает любую
+/// строку, где часть до первого `:` выглядит как токен схемы (ASCII-буквы/цифры/`+`/`-`/`.`), а
+/// значит и `example.com:8080/path`, и `localhost:8080` успешно парсятся — но с бессмысленной
+/// схемой (`example.com`, `localhost`) вместо настоящего хоста с портом. Поэтому сначала проверяем
+/// явный префикс схемы, и только это решает, подставлять ли `http://`.
 fn ensure_url_prefix(url: &str) -> String {
     if url.starts_with("http://") || url.starts_with("https://") {
         url.to_string()
@@ -125,55 +239,208 @@ fn ensure_url_prefix(url: &str) -> String {
     }
 }
 
+/// Разворачивает короткие алиасы `-t/--content-type` (`json`, `form`, `text`) в полный MIME-тип,
+/// либо возвращает входную строку без изменений, если это уже не алиас.
+fn expand_content_type(shortcut: &str) -> String {
+    match shortcut {
+        "json" => "application/json".to_string(),
+        "form" => "application/x-www-form-urlencoded".to_string(),
+        "text" => "text/plain".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Вставляет заголовок `Content-Type` в карту заголовков до отправки запроса (а не на билдере после),
+/// чтобы verbose-трейс, печатаемый перед `.send()`, показывал его вместе с остальными заголовками.
+fn insert_content_type(header_map: &mut HeaderMap, content_type: &Option<String>) {
+    let content_type = content_type.clone().map_or_else(|| "application/json".to_string(), |ct| expand_content_type(&ct));
+    if let Ok(val) = HeaderValue::from_str(&content_type) {
+        header_map.insert(CONTENT_TYPE, val);
+    }
+}
+
+/// Если `-d`/`--data` равен `-`, читает тело запроса целиком из stdin, иначе возвращает значение как есть.
+fn resolve_data(data: String) -> String {
+    if data == "-" {
+        let mut buf = String::new();
+        std::io::Read::read_to_string(&mut std::io::stdin(), &mut buf).expect("Не удалось прочитать stdin");
+        buf
+    } else {
+        data
+    }
+}
+
+fn build_url(url: &str, query: &[(String, String)]) -> Result<reqwest::Url> {
+    let mut url = reqwest::Url::parse(&ensure_url_prefix(url))?;
+    if !query.is_empty() {
+        {
+            let mut pairs = url.query_pairs_mut();
+            for (key, value) in query {
+                pairs.append_pair(key, value);
+            }
+        }
+    }
+    Ok(url)
+}
+
 fn main() {
     let args = Cli::parse();
-    let client = Client::builder().timeout(Duration::from_secs(10)).build().expect("Не удалось создать клиент");
+
+    let nofollow = args.nofollow;
+    let max_redirects = args.max_redirects;
+    let redirect_chain: std::sync::Arc<std::sync::Mutex<Vec<reqwest::Url>>> = Default::default();
+    let chain_for_policy = redirect_chain.clone();
+    let redirect_policy = reqwest::redirect::Policy::custom(move |attempt| {
+        let limit = max_redirects.unwrap_or(10);
+        if nofollow || attempt.previous().len() > limit {
+            attempt.stop()
+        } else {
+            chain_for_policy.lock().unwrap().push(attempt.url().clone());
+            attempt.follow()
+        }
+    });
+
+    let client = Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(redirect_policy)
+        .build()
+        .expect("Не удалось создать клиент");
+
+    let verbose = args.verbose;
+    let raw = args.raw;
+    let auth = build_auth_header(&args.basic, &args.bearer);
+    let output_target = resolve_output_target(args.output, args.remote_name);
 
     match args.command {
-        Commands::Get { url, headers } => {
-            let url = ensure_url_prefix(&url);
-            let response = client.get(&url).headers(build_headers(&headers)).send();
-            handle_response(response);
+        Commands::Get { url, headers, query } => {
+            let url = build_url(&url, &query).expect("Некорректный URL");
+            let header_map = build_headers(&headers, &auth);
+            trace_request(verbose, "GET", &url, &header_map);
+            let response = client.get(url.clone()).headers(header_map).send();
+            trace_response(verbose, &response, &url, &redirect_chain.lock().unwrap());
+            handle_response(response, raw, output_target);
         }
-        Commands::Post { url, data, file, headers } => {
-            let url = ensure_url_prefix(&url);
+        Commands::Post { url, data, file, headers, query, content_type } => {
+            let url = build_url(&url, &query).expect("Некорректный URL");
+            let mut header_map = build_headers(&headers, &auth);
             let response = if let Some(file_path) = file {
+                trace_request(verbose, "POST", &url, &header_map);
                 let file_content = fs::read(file_path).expect("Не удалось прочитать файл");
                 let form = multipart::Form::new().part("file", multipart::Part::bytes(file_content));
-                client.post(&url).headers(build_headers(&headers)).multipart(form).send()
+                client.post(url.clone()).headers(header_map).multipart(form).send()
             } else {
-                let mut req = client.post(&url).headers(build_headers(&headers));
+                if data.is_some() {
+                    insert_content_type(&mut header_map, &content_type);
+                }
+                trace_request(verbose, "POST", &url, &header_map);
+                let mut req = client.post(url.clone()).headers(header_map);
                 if let Some(json_data) = data {
-                    req = req.header(CONTENT_TYPE, "application/json").body(json_data);
+                    req = req.body(resolve_data(json_data));
                 }
                 req.send()
             };
-            handle_response(response);
+            trace_response(verbose, &response, &url, &redirect_chain.lock().unwrap());
+            handle_response(response, raw, output_target);
         }
-        Commands::Put { url, data, file, headers } => {
-            let url = ensure_url_prefix(&url);
+        Commands::Put { url, data, file, headers, query, content_type } => {
+            let url = build_url(&url, &query).expect("Некорректный URL");
+            let mut header_map = build_headers(&headers, &auth);
             let response = if let Some(file_path) = file {
+                trace_request(verbose, "PUT", &url, &header_map);
                 let file_content = fs::read(file_path).expect("Не удалось прочитать файл");
                 let form = multipart::Form::new().part("file", multipart::Part::bytes(file_content));
-                client.put(&url).headers(build_headers(&headers)).multipart(form).send()
+                client.put(url.clone()).headers(header_map).multipart(form).send()
             } else {
-                let mut req = client.put(&url).headers(build_headers(&headers));
+                if data.is_some() {
+                    insert_content_type(&mut header_map, &content_type);
+                }
+                trace_request(verbose, "PUT", &url, &header_map);
+                let mut req = client.put(url.clone()).headers(header_map);
                 if let Some(json_data) = data {
-                    req = req.header(CONTENT_TYPE, "application/json").body(json_data);
+                    req = req.body(resolve_data(json_data));
                 }
                 req.send()
             };
-            handle_response(response);
+            trace_response(verbose, &response, &url, &redirect_chain.lock().unwrap());
+            handle_response(response, raw, output_target);
         }
-        Commands::Delete { url, headers } => {
-            let url = ensure_url_prefix(&url);
-            let response = client.delete(&url).headers(build_headers(&headers)).send();
-            handle_response(response);
+        Commands::Patch { url, data, file, headers, query, content_type } => {
+            let url = build_url(&url, &query).expect("Некорректный URL");
+            let mut header_map = build_headers(&headers, &auth);
+            let response = if let Some(file_path) = file {
+                trace_request(verbose, "PATCH", &url, &header_map);
+                let file_content = fs::read(file_path).expect("Не удалось прочитать файл");
+                let form = multipart::Form::new().part("file", multipart::Part::bytes(file_content));
+                client.patch(url.clone()).headers(header_map).multipart(form).send()
+            } else {
+                if data.is_some() {
+                    insert_content_type(&mut header_map, &content_type);
+                }
+                trace_request(verbose, "PATCH", &url, &header_map);
+                let mut req = client.patch(url.clone()).headers(header_map);
+                if let Some(json_data) = data {
+                    req = req.body(resolve_data(json_data));
+                }
+                req.send()
+            };
+            trace_response(verbose, &response, &url, &redirect_chain.lock().unwrap());
+            handle_response(response, raw, output_target);
+        }
+        Commands::Delete { url, headers, query } => {
+            let url = build_url(&url, &query).expect("Некорректный URL");
+            let header_map = build_headers(&headers, &auth);
+            trace_request(verbose, "DELETE", &url, &header_map);
+            let response = client.delete(url.clone()).headers(header_map).send();
+            trace_response(verbose, &response, &url, &redirect_chain.lock().unwrap());
+            handle_response(response, raw, output_target);
+        }
+        Commands::Head { url, headers, query } => {
+            let url = build_url(&url, &query).expect("Некорректный URL");
+            let header_map = build_headers(&headers, &auth);
+            trace_request(verbose, "HEAD", &url, &header_map);
+            let response = client.head(url.clone()).headers(header_map).send();
+            trace_response(verbose, &response, &url, &redirect_chain.lock().unwrap());
+            handle_head_response(response);
+        }
+    }
+}
+
+/// В verbose-режиме печатает в stderr метод, итоговый URL и заголовки исходящего запроса.
+fn trace_request(verbose: bool, method: &str, url: &reqwest::Url, headers: &HeaderMap) {
+    if !verbose {
+        return;
+    }
+    eprintln!("> {} {}", method, url);
+    for (key, value) in headers.iter() {
+        eprintln!("> {}: {:?}", key, value);
+    }
+}
+
+/// В verbose-режиме печатает в stderr статус ответа и, если были редиректы, всю цепочку `Location`
+/// хопов целиком (не только первый и последний URL). `redirect_chain` собирается политикой редиректов
+/// клиента (см. `main`) через `Policy::custom`, так как сам `reqwest::blocking::Response` историю
+/// промежуточных переходов не хранит.
+fn trace_response(
+    verbose: bool,
+    response: &Result<reqwest::blocking::Response, reqwest::Error>,
+    requested_url: &reqwest::Url,
+    redirect_chain: &[reqwest::Url],
+) {
+    if !verbose {
+        return;
+    }
+    if let Ok(resp) = response {
+        eprintln!("< {}", resp.status());
+        if !redirect_chain.is_empty() {
+            let mut hops = vec![requested_url.to_string()];
+            hops.extend(redirect_chain.iter().map(|url| url.to_string()));
+            eprintln!("< Location: {}", hops.join(" -> "));
         }
     }
 }
 
-fn handle_response(response: Result<reqwest::blocking::Response, reqwest::Error>) {
+/// Как `handle_response`, но для HEAD: тело не запрашивается и не выводится, только статус и заголовки.
+fn handle_head_response(response: Result<reqwest::blocking::Response, reqwest::Error>) {
     match response {
         Ok(resp) => {
             println!("Статус: {}", resp.status());
@@ -181,13 +448,205 @@ fn handle_response(response: Result<reqwest::blocking::Response, reqwest::Error>
             for (key, value) in resp.headers().iter() {
                 println!("{}: {:?}", key, value);
             }
-            match resp.text() {
-                Ok(text) => println!("Ответ:\n{}", text),
-                Err(e) => eprintln!("Ошибка чтения ответа: {}", e),
+        }
+        Err(e) => {
+            eprintln!("Ошибка запроса: {}\nДетали: {:?}", e, e);
+        }
+    }
+}
+
+/// Куда поместить тело ответа: в stdout как обычно, в явно указанный файл (`-o`),
+/// или в файл, чьё имя определяется из ответа (`-O`).
+enum OutputTarget {
+    Stdout,
+    File(PathBuf),
+    RemoteName,
+}
+
+fn resolve_output_target(output: Option<PathBuf>, remote_name: bool) -> OutputTarget {
+    if let Some(path) = output {
+        OutputTarget::File(path)
+    } else if remote_name {
+        OutputTarget::RemoteName
+    } else {
+        OutputTarget::Stdout
+    }
+}
+
+/// Определяет имя файла для `-O`: сначала `Content-Disposition: filename=`, затем последний
+/// сегмент пути итогового URL, иначе запасное имя `download`.
+fn derive_filename(resp: &reqwest::blocking::Response) -> PathBuf {
+    let disposition = resp.headers().get(reqwest::header::CONTENT_DISPOSITION).and_then(|v| v.to_str().ok());
+    derive_filename_from(resp.url(), disposition)
+}
+
+/// Разбирает `Content-Disposition: filename=`, затем последний сегмент пути URL, иначе `download`.
+/// Вынесено из `derive_filename` отдельной функцией от `reqwest::blocking::Response`, чтобы её
+/// можно было покрыть модульными тестами без реального HTTP-ответа.
+fn derive_filename_from(url: &reqwest::Url, content_disposition: Option<&str>) -> PathBuf {
+    if let Some(disposition) = content_disposition {
+        if let Some(idx) = disposition.find("filename=") {
+            let name = disposition[idx + "filename=".len()..].trim_matches('"').trim();
+            let name = name.split(';').next().unwrap_or(name).trim().trim_matches('"');
+            if !name.is_empty() {
+                return PathBuf::from(name);
             }
         }
+    }
+    let from_url = url.path_segments().and_then(|mut segments| segments.next_back()).filter(|segment| !segment.is_empty());
+    PathBuf::from(from_url.unwrap_or("download"))
+}
+
+fn handle_response(response: Result<reqwest::blocking::Response, reqwest::Error>, raw: bool, output: OutputTarget) {
+    match response {
+        Ok(resp) => match output {
+            OutputTarget::Stdout => {
+                println!("Статус: {}", resp.status());
+                println!("Заголовки ответа:");
+                let is_json = !raw
+                    && std::io::IsTerminal::is_terminal(&std::io::stdout())
+                    && resp
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .and_then(|v| v.to_str().ok())
+                        .map(|v| v.contains("application/json") || v.contains("+json"))
+                        .unwrap_or(false);
+                for (key, value) in resp.headers().iter() {
+                    println!("{}: {:?}", key, value);
+                }
+                match resp.text() {
+                    Ok(text) => println!("Ответ:\n{}", format_body(&text, is_json)),
+                    Err(e) => eprintln!("Ошибка чтения ответа: {}", e),
+                }
+            }
+            OutputTarget::File(_) | OutputTarget::RemoteName => {
+                eprintln!("Статус: {}", resp.status());
+                eprintln!("Заголовки ответа:");
+                for (key, value) in resp.headers().iter() {
+                    eprintln!("{}: {:?}", key, value);
+                }
+                let path = match output {
+                    OutputTarget::File(path) => path,
+                    OutputTarget::RemoteName => derive_filename(&resp),
+                    OutputTarget::Stdout => unreachable!(),
+                };
+                match resp.bytes() {
+                    Ok(bytes) => match fs::write(&path, &bytes) {
+                        Ok(()) => eprintln!("Сохранено в {}", path.display()),
+                        Err(e) => eprintln!("Ошибка записи файла {}: {}", path.display(), e),
+                    },
+                    Err(e) => eprintln!("Ошибка чтения ответа: {}", e),
+                }
+            }
+        },
         Err(e) => {
             eprintln!("Ошибка запроса: {}\nДетали: {:?}", e, e);
         }
     }
 }
+
+/// Если тело похоже на JSON, `--raw` не задан и stdout — TTY, переформатирует его через
+/// `serde_json::to_string_pretty`; в остальных случаях (включая вывод в pipe/файл или ошибку
+/// разбора) возвращает текст как есть, чтобы вывод в `| cat` оставался чистым.
+fn format_body(text: &str, is_json: bool) -> String {
+    if !is_json {
+        return text.to_string();
+    }
+    match serde_json::from_str::<serde_json::Value>(text) {
+        Ok(value) => serde_json::to_string_pretty(&value).unwrap_or_else(|_| text.to_string()),
+        Err(_) => text.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ensure_url_prefix_keeps_urls_with_a_scheme() {
+        assert_eq!(ensure_url_prefix("http://example.com"), "http://example.com");
+        assert_eq!(ensure_url_prefix("https://example.com"), "https://example.com");
+    }
+
+    #[test]
+    fn ensure_url_prefix_adds_http_when_scheme_is_missing() {
+        assert_eq!(ensure_url_prefix("example.com"), "http://example.com");
+        assert_eq!(ensure_url_prefix("example.com:8080/path"), "http://example.com:8080/path");
+        assert_eq!(ensure_url_prefix("localhost:8080"), "http://localhost:8080");
+    }
+
+    #[test]
+    fn build_url_appends_and_encodes_query_pairs() {
+        let url = build_url("example.com", &[("foo".to_string(), "bar baz".to_string())]).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/?foo=bar+baz");
+    }
+
+    #[test]
+    fn build_url_without_query_leaves_url_unchanged() {
+        let url = build_url("http://example.com/path", &[]).unwrap();
+        assert_eq!(url.as_str(), "http://example.com/path");
+    }
+
+    #[test]
+    fn expand_content_type_resolves_known_aliases() {
+        assert_eq!(expand_content_type("json"), "application/json");
+        assert_eq!(expand_content_type("form"), "application/x-www-form-urlencoded");
+        assert_eq!(expand_content_type("text"), "text/plain");
+    }
+
+    #[test]
+    fn expand_content_type_passes_through_unknown_values() {
+        assert_eq!(expand_content_type("application/xml"), "application/xml");
+    }
+
+    #[test]
+    fn build_auth_header_encodes_basic_credentials() {
+        let header = build_auth_header(&Some("user:pass".to_string()), &None);
+        assert_eq!(header, Some("Basic dXNlcjpwYXNz".to_string()));
+    }
+
+    #[test]
+    fn build_auth_header_wraps_bearer_token() {
+        let header = build_auth_header(&None, &Some("mytoken".to_string()));
+        assert_eq!(header, Some("Bearer mytoken".to_string()));
+    }
+
+    #[test]
+    fn build_auth_header_prefers_basic_when_both_given() {
+        let header = build_auth_header(&Some("user:pass".to_string()), &Some("mytoken".to_string()));
+        assert_eq!(header, Some("Basic dXNlcjpwYXNz".to_string()));
+    }
+
+    #[test]
+    fn build_auth_header_is_none_without_flags() {
+        assert_eq!(build_auth_header(&None, &None), None);
+    }
+
+    #[test]
+    fn build_headers_keeps_explicit_authorization_header_over_basic_bearer_auth() {
+        let auth = build_auth_header(&Some("user:pass".to_string()), &None);
+        let headers = build_headers(&[("Authorization".to_string(), "Bearer explicit-token".to_string())], &auth);
+        assert_eq!(headers.get(AUTHORIZATION).unwrap(), "Bearer explicit-token");
+    }
+
+    #[test]
+    fn derive_filename_from_prefers_content_disposition() {
+        let url = reqwest::Url::parse("http://example.com/download").unwrap();
+        let name = derive_filename_from(&url, Some("attachment; filename=\"report.pdf\""));
+        assert_eq!(name, PathBuf::from("report.pdf"));
+    }
+
+    #[test]
+    fn derive_filename_from_falls_back_to_url_path_segment() {
+        let url = reqwest::Url::parse("http://example.com/files/archive.zip").unwrap();
+        let name = derive_filename_from(&url, None);
+        assert_eq!(name, PathBuf::from("archive.zip"));
+    }
+
+    #[test]
+    fn derive_filename_from_falls_back_to_download_for_empty_path() {
+        let url = reqwest::Url::parse("http://example.com/").unwrap();
+        let name = derive_filename_from(&url, None);
+        assert_eq!(name, PathBuf::from("download"));
+    }
+}